@@ -0,0 +1,10 @@
+//! Cross-platform inter-process communication over local sockets, with
+//! `tokio` as the default async runtime.
+//!
+//! This snapshot only carries the `unix` backend (`AF_UNIX` stream and
+//! datagram sockets); there is no Windows named-pipe backend here.
+
+#[cfg(unix)]
+mod unix;
+#[cfg(unix)]
+pub use unix::*;
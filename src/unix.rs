@@ -2,11 +2,32 @@ use futures::Stream;
 use libc::chmod;
 use std::ffi::CString;
 use std::io::{self, Error};
+use std::os::unix::io::AsRawFd;
 use std::path::Path;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
-use tokio::net::{UnixListener, UnixStream};
+
+#[cfg(feature = "tls")]
+use futures::stream::FuturesUnordered;
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+#[cfg(feature = "tls")]
+use tokio_rustls::{TlsAcceptor, TlsConnector, TlsStream};
+
+#[cfg(all(feature = "tls", feature = "rt-async-std"))]
+compile_error!("the `tls` feature requires the `rt-tokio` backend");
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "rt-async-std")] {
+        use async_std::os::unix::net::{UnixListener, UnixStream};
+        use futures::io::{AsyncRead, AsyncWrite};
+    } else if #[cfg(feature = "rt-tokio")] {
+        use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+        use tokio::net::{UnixListener, UnixStream};
+    } else {
+        compile_error!("either the `rt-tokio` or `rt-async-std` feature must be enabled");
+    }
+}
 
 /// Socket permissions and ownership on UNIX
 pub struct SecurityAttributes {
@@ -60,42 +81,172 @@ pub struct Endpoint {
     path: String,
     security_attributes: SecurityAttributes,
     unix_listener: Option<UnixListener>,
+    // Whether this endpoint bound `path` itself (and so owns applying
+    // permissions to it / removing it on drop). Abstract-namespace sockets
+    // and sockets adopted from an inherited fd have no filesystem entry of
+    // their own, so this is `false` for them.
+    owns_path: bool,
+    // User-controlled opt-out for the drop-time cleanup below, see
+    // `set_cleanup`. Only consulted when `owns_path` is true.
+    cleanup: bool,
+    // Inode of the socket file at `path` right after we successfully bound
+    // it. `None` until a bind has actually happened (e.g. `incoming()` was
+    // never called, or it failed), so drop has nothing of ours to remove.
+    // Compared against the path's current inode on drop so that we never
+    // delete a file some other process has since re-bound at the same path.
+    bound_inode: Option<u64>,
+    #[cfg(feature = "tls")]
+    tls_acceptor: Option<TlsAcceptor>,
 }
 
-struct IncomingStream<'a>(&'a mut UnixListener);
+#[cfg(not(feature = "rt-async-std"))]
+struct IncomingStream<'a> {
+    listener: &'a mut UnixListener,
+    #[cfg(feature = "tls")]
+    tls_acceptor: Option<TlsAcceptor>,
+    // Handshakes in flight, keyed by nothing in particular: we just want
+    // whichever finishes first. Kept separate from `listener` so that one
+    // slow or stalled client's handshake can't block `poll_accept` from
+    // handing out every other (already-accepted) connection in the
+    // meantime — see the `tls` request for the allow-everyone-connect case
+    // this matters for.
+    #[cfg(feature = "tls")]
+    handshakes: FuturesUnordered<tokio_rustls::Accept<UnixStream>>,
+}
 
+#[cfg(not(feature = "rt-async-std"))]
 impl<'a> Stream for IncomingStream<'a> {
-    type Item = io::Result<UnixStream>;
+    type Item = io::Result<Connection>;
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match self.0.poll_accept(cx) {
+        let this = self.get_mut();
+
+        #[cfg(feature = "tls")]
+        if let Poll::Ready(Some(result)) = Pin::new(&mut this.handshakes).poll_next(cx) {
+            return Poll::Ready(Some(match result {
+                Ok(stream) => Ok(Connection::wrap_tls(TlsStream::Server(stream))),
+                Err(err) => Err(err),
+            }));
+        }
+
+        match this.listener.poll_accept(cx) {
             Poll::Pending => Poll::Pending,
-            Poll::Ready(Ok((stream, _addr))) => Poll::Ready(Some(Ok(stream))),
             Poll::Ready(Err(inner)) => Poll::Ready(Some(Err(inner))),
+            Poll::Ready(Ok((stream, _addr))) => {
+                #[cfg(feature = "tls")]
+                if let Some(acceptor) = &this.tls_acceptor {
+                    this.handshakes.push(acceptor.accept(stream));
+                    return Pin::new(this).poll_next(cx);
+                }
+                Poll::Ready(Some(Ok(Connection::wrap(stream))))
+            }
+        }
+    }
+}
+
+// The async-std backend has no `poll_accept` on `UnixListener`; instead it
+// hands out a borrowing `Incoming` stream from `UnixListener::incoming()`,
+// which we adapt into a stream of `Connection`s.
+#[cfg(feature = "rt-async-std")]
+struct IncomingStream<'a> {
+    incoming: async_std::os::unix::net::Incoming<'a>,
+}
+
+#[cfg(feature = "rt-async-std")]
+impl<'a> Stream for IncomingStream<'a> {
+    type Item = io::Result<Connection>;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.incoming).poll_next(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Ready(Some(Err(inner))) => Poll::Ready(Some(Err(inner))),
+            Poll::Ready(Some(Ok(stream))) => Poll::Ready(Some(Ok(Connection::wrap(stream)))),
         }
     }
 }
 
 impl Endpoint {
     /// Stream of incoming connections
+    #[cfg(not(feature = "rt-async-std"))]
     pub fn incoming(
         &mut self,
-    ) -> io::Result<impl Stream<Item = tokio::io::Result<impl AsyncRead + AsyncWrite>> + '_> {
-        self.unix_listener = Some(self.inner()?);
-        unsafe {
-            // the call to bind in `inner()` creates the file
-            // `apply_permission()` will set the file permissions.
-            self.security_attributes.apply_permissions(&self.path)?;
-        };
+    ) -> io::Result<impl Stream<Item = io::Result<impl AsyncRead + AsyncWrite>> + '_> {
+        if self.unix_listener.is_none() {
+            self.unix_listener = Some(self.inner()?);
+        }
+        if self.owns_path {
+            unsafe {
+                // the call to bind in `inner()` creates the file
+                // `apply_permission()` will set the file permissions.
+                self.security_attributes.apply_permissions(&self.path)?;
+            };
+        }
+        self.record_bound_inode()?;
+        // for some unknown reason, the Incoming struct borrows the listener
+        // so we have to hold on to the listener in order to return the Incoming struct.
+        Ok(IncomingStream {
+            listener: self.unix_listener.as_mut().unwrap(),
+            #[cfg(feature = "tls")]
+            tls_acceptor: self.tls_acceptor.clone(),
+            #[cfg(feature = "tls")]
+            handshakes: FuturesUnordered::new(),
+        })
+    }
+
+    /// Stream of incoming connections
+    #[cfg(feature = "rt-async-std")]
+    pub fn incoming(
+        &mut self,
+    ) -> io::Result<impl Stream<Item = io::Result<impl AsyncRead + AsyncWrite>> + '_> {
+        if self.unix_listener.is_none() {
+            self.unix_listener = Some(self.inner()?);
+        }
+        if self.owns_path {
+            unsafe {
+                // the call to bind in `inner()` creates the file
+                // `apply_permission()` will set the file permissions.
+                self.security_attributes.apply_permissions(&self.path)?;
+            };
+        }
+        self.record_bound_inode()?;
         // for some unknown reason, the Incoming struct borrows the listener
         // so we have to hold on to the listener in order to return the Incoming struct.
-        Ok(IncomingStream(self.unix_listener.as_mut().unwrap()))
+        Ok(IncomingStream {
+            incoming: self.unix_listener.as_ref().unwrap().incoming(),
+        })
+    }
+
+    // Records the inode of the just-bound socket file, so `Drop` can later
+    // confirm it is still the file we bound before removing it. The file at
+    // `self.path` is stat-ed right after the bind in `inner()`.
+    fn record_bound_inode(&mut self) -> io::Result<()> {
+        if self.owns_path && self.bound_inode.is_none() {
+            self.bound_inode = Some(socket_file_ino(&self.path)?);
+        }
+        Ok(())
+    }
+
+    /// Controls whether this endpoint removes its socket file on drop.
+    ///
+    /// Defaults to `true`; has no effect on endpoints that never own a path
+    /// in the first place (e.g. [`Endpoint::new_abstract`] or
+    /// [`Endpoint::from_listener_fd`]).
+    pub fn set_cleanup(&mut self, cleanup: bool) {
+        self.cleanup = cleanup;
     }
 
     /// Inner platform-dependant state of the endpoint
+    #[cfg(not(feature = "rt-async-std"))]
     fn inner(&self) -> io::Result<UnixListener> {
         UnixListener::bind(&self.path)
     }
 
+    /// Inner platform-dependant state of the endpoint
+    #[cfg(feature = "rt-async-std")]
+    fn inner(&self) -> io::Result<UnixListener> {
+        async_std::task::block_on(UnixListener::bind(&self.path))
+    }
+
     /// Set security attributes for the connection
     pub fn set_security_attributes(&mut self, security_attributes: SecurityAttributes) {
         self.security_attributes = security_attributes;
@@ -106,6 +257,35 @@ impl Endpoint {
         Ok(Connection::wrap(UnixStream::connect(path.as_ref()).await?))
     }
 
+    /// Make a new TLS connection using the provided path, performing a client
+    /// handshake against `client_config` before the connection is returned.
+    ///
+    /// IPC sockets have no DNS name to validate, so a fixed placeholder name
+    /// is presented to rustls; authentication should rely on certificates
+    /// (and [`Connection::peer_cred`]) rather than server-name verification.
+    #[cfg(feature = "tls")]
+    pub async fn connect_tls<P: AsRef<Path>>(
+        path: P,
+        client_config: Arc<rustls::ClientConfig>,
+    ) -> io::Result<Connection> {
+        let stream = UnixStream::connect(path.as_ref()).await?;
+        let server_name = rustls::pki_types::ServerName::try_from("localhost")
+            .map_err(|err| Error::new(io::ErrorKind::InvalidInput, err))?
+            .to_owned();
+        let tls_stream = TlsConnector::from(client_config)
+            .connect(server_name, stream)
+            .await?;
+        Ok(Connection::wrap_tls(TlsStream::Client(tls_stream)))
+    }
+
+    /// Set the `rustls::ServerConfig` used to perform a TLS handshake with
+    /// each incoming connection. When unset, `incoming()` yields plain
+    /// connections.
+    #[cfg(feature = "tls")]
+    pub fn set_tls_config(&mut self, server_config: Arc<rustls::ServerConfig>) {
+        self.tls_acceptor = Some(TlsAcceptor::from(server_config));
+    }
+
     /// Returns the path of the endpoint.
     pub fn path(&self) -> &str {
         &self.path
@@ -117,30 +297,263 @@ impl Endpoint {
             path,
             security_attributes: SecurityAttributes::empty(),
             unix_listener: None,
+            owns_path: true,
+            cleanup: true,
+            bound_inode: None,
+            #[cfg(feature = "tls")]
+            tls_acceptor: None,
+        }
+    }
+
+    /// Bind a new endpoint in the Linux abstract socket namespace.
+    ///
+    /// An abstract address has no entry on the filesystem: the kernel
+    /// recognizes any `sockaddr_un` whose first `sun_path` byte is NUL as
+    /// referring to the abstract namespace instead of a path, keyed on the
+    /// remaining bytes (`name` here). That means there is no stale socket
+    /// file to clean up and no `chmod` race on one either — but for the same
+    /// reason, [`SecurityAttributes`] cannot be applied to it. Callers should
+    /// authorize peers with [`Connection::peer_cred`] instead.
+    #[cfg(all(target_os = "linux", not(feature = "rt-async-std")))]
+    pub fn new_abstract(name: String) -> io::Result<Self> {
+        use std::os::unix::io::FromRawFd;
+
+        // offsetof(sockaddr_un, sun_path), plus the leading NUL that marks
+        // this address as abstract, must still leave room for `name`.
+        let max_name_len =
+            std::mem::size_of::<libc::sockaddr_un>() - std::mem::size_of::<libc::sa_family_t>() - 1;
+        if name.len() > max_name_len {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "abstract socket name too long",
+            ));
+        }
+
+        let std_listener = unsafe {
+            let fd = libc::socket(
+                libc::AF_UNIX,
+                libc::SOCK_STREAM | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+                0,
+            );
+            if fd == -1 {
+                return Err(Error::last_os_error());
+            }
+
+            let mut addr: libc::sockaddr_un = std::mem::zeroed();
+            addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+            // addr.sun_path[0] is left as 0, marking this as an
+            // abstract-namespace address.
+            for (dst, src) in addr.sun_path[1..].iter_mut().zip(name.as_bytes()) {
+                *dst = *src as libc::c_char;
+            }
+            let addr_len =
+                (std::mem::size_of::<libc::sa_family_t>() + 1 + name.len()) as libc::socklen_t;
+
+            if libc::bind(fd, &addr as *const _ as *const libc::sockaddr, addr_len) == -1
+                || libc::listen(fd, 128) == -1
+            {
+                let err = Error::last_os_error();
+                libc::close(fd);
+                return Err(err);
+            }
+
+            std::os::unix::net::UnixListener::from_raw_fd(fd)
+        };
+        std_listener.set_nonblocking(true)?;
+
+        Ok(Endpoint {
+            path: format!("\0{name}"),
+            security_attributes: SecurityAttributes::empty(),
+            unix_listener: Some(UnixListener::from_std(std_listener)?),
+            owns_path: false,
+            cleanup: true,
+            bound_inode: None,
+            #[cfg(feature = "tls")]
+            tls_acceptor: None,
+        })
+    }
+
+    /// Adopt an already bound-and-listening `AF_UNIX`/`SOCK_STREAM` socket
+    /// fd, such as one passed to this process by systemd socket activation.
+    ///
+    /// Since this `Endpoint` did not create the socket, no permissions are
+    /// applied to it and its path (if it has one) is left untouched on drop.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be a valid, open file descriptor for a bound-and-listening
+    /// `AF_UNIX`/`SOCK_STREAM` socket, and must not be owned or in use by
+    /// anything else: this call takes exclusive ownership of it, and it will
+    /// be closed when the returned `Endpoint` (or the `UnixListener` produced
+    /// from it) is dropped.
+    #[cfg(not(feature = "rt-async-std"))]
+    pub unsafe fn from_listener_fd(fd: std::os::unix::io::RawFd) -> io::Result<Self> {
+        use std::os::unix::io::FromRawFd;
+
+        let std_listener = std::os::unix::net::UnixListener::from_raw_fd(fd);
+        std_listener.set_nonblocking(true)?;
+
+        Ok(Endpoint {
+            path: String::new(),
+            security_attributes: SecurityAttributes::empty(),
+            unix_listener: Some(UnixListener::from_std(std_listener)?),
+            owns_path: false,
+            cleanup: true,
+            bound_inode: None,
+            #[cfg(feature = "tls")]
+            tls_acceptor: None,
+        })
+    }
+}
+
+// Returns the inode of the socket file at `path`. This is a path lookup:
+// `fstat` on the socket's fd reports the socket's own inode, not that of
+// the filesystem entry `bind` created, so it would never match on drop.
+fn socket_file_ino(path: &str) -> io::Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(std::fs::metadata(path)?.ino())
+}
+
+// Removes the socket file at `path` only if its inode still matches
+// `bound_inode`, logging at `trace` level either way. Shared by `Endpoint`
+// and `DatagramEndpoint`'s `Drop` impls so that neither deletes a socket file
+// some other process has since rebound at the same path (e.g. a restarted
+// server that bound before we got around to cleaning up after ourselves).
+fn remove_bound_socket_file(path: &str, bound_inode: u64) {
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    match fs::metadata(path) {
+        Ok(meta) if meta.ino() == bound_inode => {
+            if fs::remove_file(Path::new(path)).is_ok() {
+                log::trace!("Removed socket file at: {}", path)
+            }
         }
+        Ok(_) => log::trace!(
+            "Not removing socket file at {}: it no longer matches the one we bound",
+            path
+        ),
+        Err(_) => {}
     }
 }
 
 impl Drop for Endpoint {
     fn drop(&mut self) {
-        use std::fs;
-        if let Ok(()) = fs::remove_file(Path::new(&self.path)) {
-            log::trace!("Removed socket file at: {}", self.path)
+        if !self.owns_path || !self.cleanup {
+            return;
+        }
+        // Nothing to remove if we never actually bound the path ourselves
+        // (e.g. `incoming()` was never called, or binding failed).
+        let Some(bound_inode) = self.bound_inode else {
+            return;
+        };
+        remove_bound_socket_file(&self.path, bound_inode);
+    }
+}
+
+/// Credentials of the process on the other end of an IPC connection.
+///
+/// On Linux/Android this is obtained via `SO_PEERCRED`, which provides all
+/// three fields. On macOS/BSD only the uid and gid are available (via
+/// `getpeereid`/`LOCAL_PEERCRED`), so `pid` is always `None` there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeerCred {
+    /// Process ID of the peer, when available.
+    pub pid: Option<i32>,
+    /// User ID of the peer.
+    pub uid: u32,
+    /// Group ID of the peer.
+    pub gid: u32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_cred(fd: std::os::unix::io::RawFd) -> io::Result<PeerCred> {
+    use std::mem;
+
+    unsafe {
+        let mut cred: libc::ucred = mem::zeroed();
+        let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+        let ret = libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        );
+        if ret == -1 {
+            return Err(Error::last_os_error());
         }
+        Ok(PeerCred {
+            pid: Some(cred.pid),
+            uid: cred.uid,
+            gid: cred.gid,
+        })
     }
 }
 
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+fn peer_cred(fd: std::os::unix::io::RawFd) -> io::Result<PeerCred> {
+    use std::mem;
+
+    unsafe {
+        let mut uid = mem::MaybeUninit::<libc::uid_t>::uninit();
+        let mut gid = mem::MaybeUninit::<libc::gid_t>::uninit();
+        let ret = libc::getpeereid(fd, uid.as_mut_ptr(), gid.as_mut_ptr());
+        if ret == -1 {
+            return Err(Error::last_os_error());
+        }
+        Ok(PeerCred {
+            pid: None,
+            uid: uid.assume_init(),
+            gid: gid.assume_init(),
+        })
+    }
+}
+
+enum ConnectionInner {
+    Plain(UnixStream),
+    #[cfg(feature = "tls")]
+    Tls(Box<TlsStream<UnixStream>>),
+}
+
 /// IPC connection.
 pub struct Connection {
-    inner: UnixStream,
+    inner: ConnectionInner,
 }
 
 impl Connection {
     fn wrap(stream: UnixStream) -> Self {
-        Self { inner: stream }
+        Self {
+            inner: ConnectionInner::Plain(stream),
+        }
+    }
+
+    #[cfg(feature = "tls")]
+    fn wrap_tls(stream: TlsStream<UnixStream>) -> Self {
+        Self {
+            inner: ConnectionInner::Tls(Box::new(stream)),
+        }
+    }
+
+    /// Returns the UID, GID and (where available) PID of the process on the
+    /// other end of this connection.
+    pub fn peer_cred(&self) -> io::Result<PeerCred> {
+        let fd = match &self.inner {
+            ConnectionInner::Plain(stream) => stream.as_raw_fd(),
+            #[cfg(feature = "tls")]
+            ConnectionInner::Tls(stream) => stream.get_ref().0.as_raw_fd(),
+        };
+        peer_cred(fd)
     }
 }
 
+#[cfg(not(feature = "rt-async-std"))]
 impl AsyncRead for Connection {
     fn poll_read(
         self: Pin<&mut Self>,
@@ -148,10 +561,15 @@ impl AsyncRead for Connection {
         buf: &mut ReadBuf,
     ) -> Poll<io::Result<()>> {
         let this = Pin::into_inner(self);
-        Pin::new(&mut this.inner).poll_read(ctx, buf)
+        match &mut this.inner {
+            ConnectionInner::Plain(stream) => Pin::new(stream).poll_read(ctx, buf),
+            #[cfg(feature = "tls")]
+            ConnectionInner::Tls(stream) => Pin::new(stream.as_mut()).poll_read(ctx, buf),
+        }
     }
 }
 
+#[cfg(not(feature = "rt-async-std"))]
 impl AsyncWrite for Connection {
     fn poll_write(
         self: Pin<&mut Self>,
@@ -159,16 +577,820 @@ impl AsyncWrite for Connection {
         buf: &[u8],
     ) -> Poll<Result<usize, io::Error>> {
         let this = Pin::into_inner(self);
-        Pin::new(&mut this.inner).poll_write(ctx, buf)
+        match &mut this.inner {
+            ConnectionInner::Plain(stream) => Pin::new(stream).poll_write(ctx, buf),
+            #[cfg(feature = "tls")]
+            ConnectionInner::Tls(stream) => Pin::new(stream.as_mut()).poll_write(ctx, buf),
+        }
     }
 
     fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
         let this = Pin::into_inner(self);
-        Pin::new(&mut this.inner).poll_flush(ctx)
+        match &mut this.inner {
+            ConnectionInner::Plain(stream) => Pin::new(stream).poll_flush(ctx),
+            #[cfg(feature = "tls")]
+            ConnectionInner::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(ctx),
+        }
     }
 
     fn poll_shutdown(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
         let this = Pin::into_inner(self);
-        Pin::new(&mut this.inner).poll_shutdown(ctx)
+        match &mut this.inner {
+            ConnectionInner::Plain(stream) => Pin::new(stream).poll_shutdown(ctx),
+            #[cfg(feature = "tls")]
+            ConnectionInner::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(ctx),
+        }
+    }
+}
+
+// The async-std backend uses `futures::io::{AsyncRead, AsyncWrite}`, whose
+// `poll_read`/`poll_write` operate on plain `&mut [u8]` buffers rather than
+// tokio's `ReadBuf`, so `Connection` gets a second set of impls here instead
+// of sharing the tokio ones above.
+#[cfg(feature = "rt-async-std")]
+impl AsyncRead for Connection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = Pin::into_inner(self);
+        match &mut this.inner {
+            ConnectionInner::Plain(stream) => Pin::new(stream).poll_read(ctx, buf),
+        }
+    }
+}
+
+#[cfg(feature = "rt-async-std")]
+impl AsyncWrite for Connection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = Pin::into_inner(self);
+        match &mut this.inner {
+            ConnectionInner::Plain(stream) => Pin::new(stream).poll_write(ctx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = Pin::into_inner(self);
+        match &mut this.inner {
+            ConnectionInner::Plain(stream) => Pin::new(stream).poll_flush(ctx),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = Pin::into_inner(self);
+        match &mut this.inner {
+            ConnectionInner::Plain(stream) => Pin::new(stream).poll_close(ctx),
+        }
+    }
+}
+
+// `UnixDatagram` only has a tokio-backed implementation here; the
+// async-std backend has no datagram support yet.
+#[cfg(not(feature = "rt-async-std"))]
+use tokio::net::UnixDatagram;
+
+/// Endpoint for connectionless, message-boundary-preserving IPC, built on
+/// `SOCK_DGRAM` unix sockets. Unlike [`Endpoint`], there is no connection to
+/// accept: once bound, datagrams from any sender can be received with
+/// [`DatagramEndpoint::recv_from`] and replies sent with
+/// [`DatagramEndpoint::send_to`].
+#[cfg(not(feature = "rt-async-std"))]
+pub struct DatagramEndpoint {
+    path: String,
+    socket: UnixDatagram,
+    // Opt-out for the drop-time cleanup below, see `set_cleanup`.
+    cleanup: bool,
+    // Inode of the socket file right after we bound it, compared against the
+    // path's current inode on drop so we never delete a file some other
+    // process has since rebound at the same path.
+    bound_inode: u64,
+}
+
+#[cfg(not(feature = "rt-async-std"))]
+impl DatagramEndpoint {
+    /// Bind a new datagram endpoint at the given path, using the default
+    /// [`SecurityAttributes`].
+    pub fn bind(path: String) -> io::Result<Self> {
+        Self::bind_with_security_attributes(path, SecurityAttributes::empty())
+    }
+
+    /// Bind a new datagram endpoint at the given path, applying
+    /// `security_attributes` to the resulting socket file.
+    pub fn bind_with_security_attributes(
+        path: String,
+        security_attributes: SecurityAttributes,
+    ) -> io::Result<Self> {
+        let socket = UnixDatagram::bind(&path)?;
+        unsafe {
+            // the call to bind above creates the file;
+            // `apply_permissions()` will set the file permissions.
+            security_attributes.apply_permissions(&path)?;
+        };
+        // Record the inode of the file the bind above just created.
+        let bound_inode = socket_file_ino(&path)?;
+        Ok(DatagramEndpoint {
+            path,
+            socket,
+            cleanup: true,
+            bound_inode,
+        })
+    }
+
+    /// Send `buf` as a single datagram to the socket at `path`.
+    pub async fn send_to<P: AsRef<Path>>(&self, buf: &[u8], path: P) -> io::Result<usize> {
+        self.socket.send_to(buf, path.as_ref()).await
+    }
+
+    /// Receive a single datagram, returning its length and the sender's address.
+    pub async fn recv_from(
+        &self,
+        buf: &mut [u8],
+    ) -> io::Result<(usize, tokio::net::unix::SocketAddr)> {
+        self.socket.recv_from(buf).await
+    }
+
+    /// Returns the path of the endpoint.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Controls whether this endpoint removes its socket file on drop.
+    /// Defaults to `true`.
+    pub fn set_cleanup(&mut self, cleanup: bool) {
+        self.cleanup = cleanup;
+    }
+}
+
+#[cfg(not(feature = "rt-async-std"))]
+impl Drop for DatagramEndpoint {
+    fn drop(&mut self) {
+        if !self.cleanup {
+            return;
+        }
+        remove_bound_socket_file(&self.path, self.bound_inode);
+    }
+}
+
+/// A datagram socket connected to a single peer, analogous to [`Connection`]
+/// but preserving message boundaries instead of exposing a byte stream.
+#[cfg(not(feature = "rt-async-std"))]
+pub struct DatagramConnection {
+    socket: UnixDatagram,
+}
+
+#[cfg(not(feature = "rt-async-std"))]
+impl DatagramConnection {
+    /// Create an unbound datagram socket and connect it to the
+    /// [`DatagramEndpoint`] at the given path.
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path.as_ref())?;
+        Ok(DatagramConnection { socket })
+    }
+
+    /// Send `buf` as a single datagram to the connected peer.
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.socket.send(buf).await
+    }
+
+    /// Receive a single datagram from the connected peer.
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.socket.recv(buf).await
+    }
+}
+
+/// Borrowing read half of a [`Connection`], obtained via [`Connection::split`].
+///
+/// Plain connections are split via [`tokio::net::UnixStream::split`],
+/// which is lock-free; TLS connections fall back to the generic, mutex-backed
+/// [`tokio::io::split`] since `tokio-rustls` has no dedicated split of its own.
+#[cfg(not(feature = "rt-async-std"))]
+pub enum ReadHalf<'a> {
+    Plain(tokio::net::unix::ReadHalf<'a>),
+    #[cfg(feature = "tls")]
+    Generic(tokio::io::ReadHalf<&'a mut Connection>),
+}
+/// Borrowing write half of a [`Connection`], obtained via [`Connection::split`].
+#[cfg(not(feature = "rt-async-std"))]
+pub enum WriteHalf<'a> {
+    Plain(tokio::net::unix::WriteHalf<'a>),
+    #[cfg(feature = "tls")]
+    Generic(tokio::io::WriteHalf<&'a mut Connection>),
+}
+
+#[cfg(not(feature = "rt-async-std"))]
+impl AsyncRead for ReadHalf<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut ReadBuf,
+    ) -> Poll<io::Result<()>> {
+        match Pin::into_inner(self) {
+            ReadHalf::Plain(half) => Pin::new(half).poll_read(ctx, buf),
+            #[cfg(feature = "tls")]
+            ReadHalf::Generic(half) => Pin::new(half).poll_read(ctx, buf),
+        }
+    }
+}
+
+#[cfg(not(feature = "rt-async-std"))]
+impl AsyncWrite for WriteHalf<'_> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match Pin::into_inner(self) {
+            WriteHalf::Plain(half) => Pin::new(half).poll_write(ctx, buf),
+            #[cfg(feature = "tls")]
+            WriteHalf::Generic(half) => Pin::new(half).poll_write(ctx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match Pin::into_inner(self) {
+            WriteHalf::Plain(half) => Pin::new(half).poll_flush(ctx),
+            #[cfg(feature = "tls")]
+            WriteHalf::Generic(half) => Pin::new(half).poll_flush(ctx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match Pin::into_inner(self) {
+            WriteHalf::Plain(half) => Pin::new(half).poll_shutdown(ctx),
+            #[cfg(feature = "tls")]
+            WriteHalf::Generic(half) => Pin::new(half).poll_shutdown(ctx),
+        }
+    }
+}
+
+/// Borrowing read half of a [`Connection`], obtained via [`Connection::split`].
+#[cfg(feature = "rt-async-std")]
+pub type ReadHalf<'a> = futures::io::ReadHalf<&'a mut Connection>;
+/// Borrowing write half of a [`Connection`], obtained via [`Connection::split`].
+#[cfg(feature = "rt-async-std")]
+pub type WriteHalf<'a> = futures::io::WriteHalf<&'a mut Connection>;
+
+// Plain connections split via `UnixStream::into_split`, which is lock-free
+// (each half talks to the socket directly); TLS connections have no native
+// split, so they fall back to the generic, mutex-backed `tokio::io::split`.
+#[cfg(not(feature = "rt-async-std"))]
+enum OwnedReadHalfInner {
+    Plain(tokio::net::unix::OwnedReadHalf),
+    #[cfg(feature = "tls")]
+    Generic(tokio::io::ReadHalf<Connection>),
+}
+#[cfg(not(feature = "rt-async-std"))]
+enum OwnedWriteHalfInner {
+    Plain(tokio::net::unix::OwnedWriteHalf),
+    #[cfg(feature = "tls")]
+    Generic(tokio::io::WriteHalf<Connection>),
+}
+
+/// Owned read half of a [`Connection`], obtained via [`Connection::into_split`].
+///
+/// Can be moved into a separate task from its [`OwnedWriteHalf`] counterpart
+/// and later recombined with [`OwnedReadHalf::reunite`].
+#[cfg(not(feature = "rt-async-std"))]
+pub struct OwnedReadHalf(OwnedReadHalfInner);
+/// Owned write half of a [`Connection`], obtained via [`Connection::into_split`].
+///
+/// On a plain connection, dropping this half shuts down the write direction
+/// of the underlying socket, matching `tokio::net::unix::OwnedWriteHalf`. On
+/// a TLS connection, dropping it does not shut the socket down.
+#[cfg(not(feature = "rt-async-std"))]
+pub struct OwnedWriteHalf(OwnedWriteHalfInner);
+
+#[cfg(not(feature = "rt-async-std"))]
+impl OwnedReadHalf {
+    /// Reunites this half with its [`OwnedWriteHalf`] counterpart into the
+    /// original `Connection`.
+    ///
+    /// Returns an error if the two halves did not originate from the same
+    /// [`Connection::into_split`] call. Mismatched halves of a TLS connection
+    /// panic instead, matching `tokio::io::ReadHalf::unsplit`, since the
+    /// generic split gives us no cheaper way to check.
+    pub fn reunite(self, write: OwnedWriteHalf) -> io::Result<Connection> {
+        match (self.0, write.0) {
+            (OwnedReadHalfInner::Plain(read), OwnedWriteHalfInner::Plain(write)) => read
+                .reunite(write)
+                .map(Connection::wrap)
+                .map_err(|err| Error::new(io::ErrorKind::InvalidInput, err)),
+            #[cfg(feature = "tls")]
+            (OwnedReadHalfInner::Generic(read), OwnedWriteHalfInner::Generic(write)) => {
+                Ok(read.unsplit(write))
+            }
+            #[cfg(feature = "tls")]
+            _ => Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "tried to reunite halves from different connections",
+            )),
+        }
+    }
+}
+
+#[cfg(not(feature = "rt-async-std"))]
+impl AsyncRead for OwnedReadHalf {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut ReadBuf,
+    ) -> Poll<io::Result<()>> {
+        match &mut Pin::into_inner(self).0 {
+            OwnedReadHalfInner::Plain(half) => Pin::new(half).poll_read(ctx, buf),
+            #[cfg(feature = "tls")]
+            OwnedReadHalfInner::Generic(half) => Pin::new(half).poll_read(ctx, buf),
+        }
+    }
+}
+
+#[cfg(not(feature = "rt-async-std"))]
+impl AsyncWrite for OwnedWriteHalf {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut Pin::into_inner(self).0 {
+            OwnedWriteHalfInner::Plain(half) => Pin::new(half).poll_write(ctx, buf),
+            #[cfg(feature = "tls")]
+            OwnedWriteHalfInner::Generic(half) => Pin::new(half).poll_write(ctx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut Pin::into_inner(self).0 {
+            OwnedWriteHalfInner::Plain(half) => Pin::new(half).poll_flush(ctx),
+            #[cfg(feature = "tls")]
+            OwnedWriteHalfInner::Generic(half) => Pin::new(half).poll_flush(ctx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut Pin::into_inner(self).0 {
+            OwnedWriteHalfInner::Plain(half) => Pin::new(half).poll_shutdown(ctx),
+            #[cfg(feature = "tls")]
+            OwnedWriteHalfInner::Generic(half) => Pin::new(half).poll_shutdown(ctx),
+        }
+    }
+}
+
+/// Owned read half of a [`Connection`], obtained via [`Connection::into_split`].
+///
+/// Can be moved into a separate task from its [`OwnedWriteHalf`] counterpart
+/// and later recombined with [`OwnedReadHalf::reunite`].
+#[cfg(feature = "rt-async-std")]
+pub struct OwnedReadHalf(futures::io::ReadHalf<Connection>);
+/// Owned write half of a [`Connection`], obtained via [`Connection::into_split`].
+#[cfg(feature = "rt-async-std")]
+pub struct OwnedWriteHalf(futures::io::WriteHalf<Connection>);
+
+#[cfg(feature = "rt-async-std")]
+impl OwnedReadHalf {
+    /// Reunites this half with its [`OwnedWriteHalf`] counterpart into the
+    /// original `Connection`, failing if the two halves were not split from
+    /// the same connection.
+    pub fn reunite(self, write: OwnedWriteHalf) -> io::Result<Connection> {
+        self.0
+            .reunite(write.0)
+            .map_err(|err| Error::new(io::ErrorKind::InvalidInput, err))
+    }
+}
+
+#[cfg(feature = "rt-async-std")]
+impl AsyncRead for OwnedReadHalf {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).0).poll_read(ctx, buf)
+    }
+}
+
+#[cfg(feature = "rt-async-std")]
+impl AsyncWrite for OwnedWriteHalf {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).0).poll_write(ctx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).0).poll_flush(ctx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).0).poll_close(ctx)
+    }
+}
+
+impl Connection {
+    /// Split this connection into independently-owned read and write halves
+    /// that can be moved into separate tasks, reuniting them later with
+    /// [`OwnedReadHalf::reunite`].
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        #[cfg(not(feature = "rt-async-std"))]
+        {
+            match self.inner {
+                ConnectionInner::Plain(stream) => {
+                    let (read, write) = stream.into_split();
+                    (
+                        OwnedReadHalf(OwnedReadHalfInner::Plain(read)),
+                        OwnedWriteHalf(OwnedWriteHalfInner::Plain(write)),
+                    )
+                }
+                #[cfg(feature = "tls")]
+                ConnectionInner::Tls(stream) => {
+                    let (read, write) = tokio::io::split(Connection {
+                        inner: ConnectionInner::Tls(stream),
+                    });
+                    (
+                        OwnedReadHalf(OwnedReadHalfInner::Generic(read)),
+                        OwnedWriteHalf(OwnedWriteHalfInner::Generic(write)),
+                    )
+                }
+            }
+        }
+        #[cfg(feature = "rt-async-std")]
+        {
+            let (read, write) = futures::io::AsyncReadExt::split(self);
+            (OwnedReadHalf(read), OwnedWriteHalf(write))
+        }
+    }
+
+    /// Borrow this connection as independent read and write halves, without
+    /// giving up ownership of the `Connection`.
+    pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        #[cfg(not(feature = "rt-async-std"))]
+        {
+            #[cfg(feature = "tls")]
+            if matches!(self.inner, ConnectionInner::Tls(_)) {
+                let (read, write) = tokio::io::split(self);
+                return (ReadHalf::Generic(read), WriteHalf::Generic(write));
+            }
+            match &mut self.inner {
+                ConnectionInner::Plain(stream) => {
+                    let (read, write) = stream.split();
+                    (ReadHalf::Plain(read), WriteHalf::Plain(write))
+                }
+                #[cfg(feature = "tls")]
+                ConnectionInner::Tls(_) => unreachable!("handled above"),
+            }
+        }
+        #[cfg(feature = "rt-async-std")]
+        {
+            futures::io::AsyncReadExt::split(self)
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "rt-async-std")))]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn unique_socket_path() -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        std::env::temp_dir()
+            .join(format!(
+                "parity-tokio-ipc-test-{}-{}.sock",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    // The server task's `incoming()` call may not have bound the socket yet
+    // by the time we try to connect, since the `#[tokio::test]` runtime
+    // doesn't guarantee it has been polled. Retry briefly instead of
+    // requiring the caller to synchronize with the server task.
+    async fn connect_with_retries(path: &str) -> Connection {
+        for _ in 0..100 {
+            match Endpoint::connect(path).await {
+                Ok(conn) => return conn,
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+            }
+        }
+        Endpoint::connect(path).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn peer_cred_matches_self() {
+        let path = unique_socket_path();
+        let mut endpoint = Endpoint::new(path.clone());
+        let server = tokio::spawn(async move {
+            let mut incoming = endpoint.incoming().unwrap();
+            incoming.next().await.unwrap().unwrap()
+        });
+
+        let client = connect_with_retries(&path).await;
+        let _server_conn = server.await.unwrap();
+
+        // Both ends of this loopback connection belong to this test process,
+        // so the peer's credentials are just our own.
+        let cred = client.peer_cred().unwrap();
+        unsafe {
+            assert_eq!(cred.uid, libc::getuid());
+            assert_eq!(cred.gid, libc::getgid());
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        assert_eq!(cred.pid, Some(std::process::id() as i32));
+    }
+
+    #[tokio::test]
+    async fn from_listener_fd_accepts_connections() {
+        use std::os::unix::io::IntoRawFd;
+
+        let path = unique_socket_path();
+        let std_listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let mut endpoint =
+            unsafe { Endpoint::from_listener_fd(std_listener.into_raw_fd()) }.unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut incoming = endpoint.incoming().unwrap();
+            let mut conn = incoming.next().await.unwrap().unwrap();
+            let mut buf = [0u8; 5];
+            conn.read_exact(&mut buf).await.unwrap();
+            buf
+        });
+
+        let mut client = connect_with_retries(&path).await;
+        client.write_all(b"hello").await.unwrap();
+
+        assert_eq!(&server.await.unwrap(), b"hello");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn into_split_halves_work_independently_and_reunite() {
+        let path = unique_socket_path();
+        let mut endpoint = Endpoint::new(path.clone());
+
+        let server = tokio::spawn(async move {
+            let mut incoming = endpoint.incoming().unwrap();
+            let mut conns = Vec::new();
+            for _ in 0..3 {
+                conns.push(incoming.next().await.unwrap().unwrap());
+            }
+            let mut buf = [0u8; 5];
+            conns[0].read_exact(&mut buf).await.unwrap();
+            conns[0].write_all(b"world").await.unwrap();
+        });
+
+        let client1 = connect_with_retries(&path).await;
+        let (mut read1, mut write1) = client1.into_split();
+
+        // The two halves of one connection can be driven from separate tasks.
+        let writer = tokio::spawn(async move {
+            write1.write_all(b"hello").await.unwrap();
+            write1
+        });
+        let reader = tokio::spawn(async move {
+            let mut buf = [0u8; 5];
+            read1.read_exact(&mut buf).await.unwrap();
+            (read1, buf)
+        });
+
+        let (_read2, write2) = connect_with_retries(&path).await.into_split();
+        let (read3, write3) = connect_with_retries(&path).await.into_split();
+        server.await.unwrap();
+        let _write1 = writer.await.unwrap();
+        let (read1, buf) = reader.await.unwrap();
+        assert_eq!(&buf, b"world");
+
+        // Mismatched halves (from different connections) must not reunite,
+        // matching `tokio::net::unix::OwnedReadHalf::reunite`.
+        assert!(read1.reunite(write2).is_err());
+
+        // Matching halves reunite back into a single `Connection`.
+        read3.reunite(write3).unwrap();
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "tls")]
+    fn self_signed_tls_configs() -> (Arc<rustls::ServerConfig>, Arc<rustls::ClientConfig>) {
+        let cert_key = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert = cert_key.cert.der().clone();
+        let key = rustls::pki_types::PrivatePkcs8KeyDer::from(cert_key.key_pair.serialize_der());
+
+        let server_config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert.clone()], key.into())
+            .unwrap();
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(cert).unwrap();
+        let client_config = rustls::ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        (Arc::new(server_config), Arc::new(client_config))
+    }
+
+    // A stalled client that never completes its TLS handshake must not
+    // prevent a second, well-behaved client from being accepted and
+    // handshaked — see the `IncomingStream::poll_next` fix this guards.
+    #[cfg(feature = "tls")]
+    #[tokio::test]
+    async fn tls_handshake_does_not_block_on_stalled_peer() {
+        let (server_config, client_config) = self_signed_tls_configs();
+        let path = unique_socket_path();
+        let mut endpoint = Endpoint::new(path.clone());
+        endpoint.set_tls_config(server_config);
+
+        let server = tokio::spawn(async move {
+            let mut incoming = endpoint.incoming().unwrap();
+            let mut conn = tokio::time::timeout(std::time::Duration::from_secs(3), incoming.next())
+                .await
+                .expect("a stalled handshake must not block other connections")
+                .unwrap()
+                .unwrap();
+            let mut buf = [0u8; 5];
+            conn.read_exact(&mut buf).await.unwrap();
+            buf
+        });
+
+        // Connect but never send a ClientHello: its handshake future sits
+        // forever in `IncomingStream::handshakes`.
+        let _stalled = connect_with_retries(&path).await;
+
+        let mut client = Endpoint::connect_tls(&path, client_config).await.unwrap();
+        client.write_all(b"hello").await.unwrap();
+
+        assert_eq!(&server.await.unwrap(), b"hello");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn new_abstract_accepts_connections() {
+        let name = format!(
+            "parity-tokio-ipc-test-abstract-{}-{}",
+            std::process::id(),
+            unique_socket_path()
+        );
+        let mut endpoint = Endpoint::new_abstract(name.clone()).unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut incoming = endpoint.incoming().unwrap();
+            let mut conn = incoming.next().await.unwrap().unwrap();
+            let mut buf = [0u8; 4];
+            conn.read_exact(&mut buf).await.unwrap();
+            buf
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // `Endpoint::connect`'s path-based API can't represent an abstract
+        // address (its leading NUL byte is rejected by `CString::new`), so
+        // connect with a raw socket instead, the same way a systemd-activated
+        // peer would.
+        tokio::task::spawn_blocking(move || unsafe {
+            let fd = libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0);
+            assert!(fd >= 0);
+            let mut addr: libc::sockaddr_un = std::mem::zeroed();
+            addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+            for (dst, src) in addr.sun_path[1..].iter_mut().zip(name.as_bytes()) {
+                *dst = *src as libc::c_char;
+            }
+            let addr_len =
+                (std::mem::size_of::<libc::sa_family_t>() + 1 + name.len()) as libc::socklen_t;
+            assert_eq!(
+                libc::connect(fd, &addr as *const _ as *const libc::sockaddr, addr_len),
+                0
+            );
+            assert_eq!(
+                libc::write(fd, b"ping".as_ptr() as *const libc::c_void, 4),
+                4
+            );
+            libc::close(fd);
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(&server.await.unwrap(), b"ping");
+    }
+
+    #[tokio::test]
+    async fn cleanup_is_race_safe_and_can_be_disabled() {
+        let path = unique_socket_path();
+        {
+            let mut endpoint = Endpoint::new(path.clone());
+            let _incoming = endpoint.incoming().unwrap();
+            assert!(Path::new(&path).exists());
+
+            // Simulate another process rebinding the same path right after
+            // we're done with it, before we've actually dropped.
+            std::fs::remove_file(&path).unwrap();
+            std::fs::write(&path, b"not a socket").unwrap();
+        } // `endpoint` drops here.
+        assert!(
+            Path::new(&path).exists(),
+            "drop must not remove a file it didn't bind"
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        let path = unique_socket_path();
+        {
+            let mut endpoint = Endpoint::new(path.clone());
+            endpoint.set_cleanup(false);
+            let _incoming = endpoint.incoming().unwrap();
+        }
+        assert!(
+            Path::new(&path).exists(),
+            "set_cleanup(false) must leave the socket file in place"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn datagram_connection_sends_to_bound_endpoint() {
+        let path = unique_socket_path();
+        let endpoint = DatagramEndpoint::bind(path.clone()).unwrap();
+        let peer = DatagramConnection::connect(&path).unwrap();
+
+        peer.send(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        let (len, _sender_addr) = endpoint.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..len], b"ping");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn datagram_endpoints_round_trip_via_send_to_recv_from() {
+        let path_a = unique_socket_path();
+        let path_b = unique_socket_path();
+        let a = DatagramEndpoint::bind(path_a.clone()).unwrap();
+        let b = DatagramEndpoint::bind(path_b.clone()).unwrap();
+
+        a.send_to(b"ping", &path_b).await.unwrap();
+        let mut buf = [0u8; 4];
+        let (len, sender_addr) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..len], b"ping");
+
+        b.send_to(b"pong", sender_addr.as_pathname().unwrap())
+            .await
+            .unwrap();
+        let mut buf = [0u8; 4];
+        let (len, _) = a.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..len], b"pong");
+
+        std::fs::remove_file(&path_a).unwrap();
+        std::fs::remove_file(&path_b).unwrap();
+    }
+}
+
+#[cfg(all(test, feature = "rt-async-std"))]
+mod async_std_tests {
+    use super::*;
+    use futures::{AsyncReadExt, AsyncWriteExt, StreamExt};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn unique_socket_path() -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        std::env::temp_dir()
+            .join(format!(
+                "parity-tokio-ipc-test-{}-{}.sock",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[async_std::test]
+    async fn incoming_accepts_connections() {
+        let path = unique_socket_path();
+        let mut endpoint = Endpoint::new(path.clone());
+
+        let server = async_std::task::spawn(async move {
+            let mut incoming = endpoint.incoming().unwrap();
+            let mut conn = incoming.next().await.unwrap().unwrap();
+            let mut buf = [0u8; 5];
+            conn.read_exact(&mut buf).await.unwrap();
+            buf
+        });
+
+        let mut client = loop {
+            match Endpoint::connect(&path).await {
+                Ok(conn) => break conn,
+                Err(_) => async_std::task::sleep(std::time::Duration::from_millis(10)).await,
+            }
+        };
+        client.write_all(b"hello").await.unwrap();
+
+        assert_eq!(&server.await, b"hello");
+        let _ = std::fs::remove_file(&path);
     }
 }